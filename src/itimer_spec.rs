@@ -1,6 +1,7 @@
 use std::time::Duration;
 use nix::sys::time::{TimeSpec, TimeValLike};
-pub use libc::{CLOCK_REALTIME, CLOCK_MONOTONIC};
+pub use libc::{CLOCK_REALTIME, CLOCK_MONOTONIC, CLOCK_BOOTTIME, CLOCK_REALTIME_ALARM,
+               CLOCK_BOOTTIME_ALARM};
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -55,6 +56,55 @@ impl From<Duration> for ITimerSpec {
     }
 }
 
+/// A higher-level description of how a timer should expire, used by `TimerFd::arm`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Expiration {
+    /// Expire once after the given duration and then stay disarmed
+    OneShot(TimeSpec),
+    /// Expire after the given duration, then again every `TimeSpec` thereafter
+    Interval(TimeSpec),
+    /// Expire after the first `TimeSpec` (the initial delay), then every second `TimeSpec`
+    /// (the repeat period) thereafter
+    IntervalDelayed(TimeSpec, TimeSpec),
+}
+
+impl From<Expiration> for ITimerSpec {
+    fn from(expiration: Expiration) -> Self {
+        match expiration {
+            Expiration::OneShot(value) => {
+                ITimerSpec {
+                    it_interval: TimeSpec::zero(),
+                    it_value: value,
+                }
+            }
+            Expiration::Interval(value) => {
+                ITimerSpec {
+                    it_interval: value,
+                    it_value: value,
+                }
+            }
+            Expiration::IntervalDelayed(value, interval) => {
+                ITimerSpec {
+                    it_interval: interval,
+                    it_value: value,
+                }
+            }
+        }
+    }
+}
+
+impl From<ITimerSpec> for Expiration {
+    fn from(itimerspec: ITimerSpec) -> Self {
+        if itimerspec.it_interval == TimeSpec::zero() {
+            Expiration::OneShot(itimerspec.it_value)
+        } else if itimerspec.it_interval == itimerspec.it_value {
+            Expiration::Interval(itimerspec.it_value)
+        } else {
+            Expiration::IntervalDelayed(itimerspec.it_value, itimerspec.it_interval)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,6 +123,40 @@ mod tests {
             concat!("Alignment of ", stringify!(ITimerSpec))
         );
     }
+
+    #[test]
+    fn test_expiration_one_shot_round_trip() {
+        let itimerspec: ITimerSpec = Expiration::OneShot(TimeSpec::seconds(3)).into();
+        assert_eq!(itimerspec.it_interval, TimeSpec::zero());
+        assert_eq!(itimerspec.it_value, TimeSpec::seconds(3));
+        assert_eq!(
+            Expiration::from(itimerspec),
+            Expiration::OneShot(TimeSpec::seconds(3))
+        );
+    }
+
+    #[test]
+    fn test_expiration_interval_round_trip() {
+        let itimerspec: ITimerSpec = Expiration::Interval(TimeSpec::seconds(2)).into();
+        assert_eq!(itimerspec.it_interval, TimeSpec::seconds(2));
+        assert_eq!(itimerspec.it_value, TimeSpec::seconds(2));
+        assert_eq!(
+            Expiration::from(itimerspec),
+            Expiration::Interval(TimeSpec::seconds(2))
+        );
+    }
+
+    #[test]
+    fn test_expiration_interval_delayed_round_trip() {
+        let itimerspec: ITimerSpec =
+            Expiration::IntervalDelayed(TimeSpec::seconds(5), TimeSpec::seconds(1)).into();
+        assert_eq!(itimerspec.it_interval, TimeSpec::seconds(1));
+        assert_eq!(itimerspec.it_value, TimeSpec::seconds(5));
+        assert_eq!(
+            Expiration::from(itimerspec),
+            Expiration::IntervalDelayed(TimeSpec::seconds(5), TimeSpec::seconds(1))
+        );
+    }
 }
 
 #[inline]
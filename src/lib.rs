@@ -5,15 +5,22 @@ extern crate libc;
 
 mod itimer_spec;
 mod sys;
+mod timer;
+#[cfg(feature = "async")]
+mod stream;
 
 use std::mem;
 use std::ptr;
-use std::os::unix::io::{RawFd, AsRawFd};
+use std::os::unix::io::{RawFd, AsRawFd, FromRawFd, IntoRawFd};
 use nix::{Errno, Error};
 use nix::unistd;
+use nix::sys::time::TimeSpec;
 use libc::{c_int, clockid_t};
 
 pub use self::itimer_spec::*;
+pub use self::timer::{Timer, FakeClock, FakeTimer};
+#[cfg(feature = "async")]
+pub use self::stream::TimerFdStream;
 
 #[doc(hidden)]
 const TIMERFD_DATA_SIZE: usize = 8;
@@ -75,6 +82,18 @@ pub fn timerfd_gettime(fd: RawFd, otmr: &mut ITimerSpec) -> nix::Result<()> {
     Ok(())
 }
 
+/// Return the resolution of `clock_id`, i.e. the smallest interval the kernel can represent for
+/// that clock
+#[inline]
+pub fn clock_resolution(clock_id: ClockId) -> nix::Result<TimeSpec> {
+    let mut res: libc::timespec = unsafe { mem::zeroed() };
+    let ret = unsafe { sys::clock_getres(clock_id as clockid_t, &mut res) };
+    if ret == -1 {
+        return Err(Error::last());
+    }
+    Ok(unsafe { mem::transmute::<libc::timespec, TimeSpec>(res) })
+}
+
 #[repr(i32)]
 #[derive(Debug, Copy, Clone)]
 pub enum ClockId {
@@ -82,6 +101,12 @@ pub enum ClockId {
     Realtime = CLOCK_REALTIME,
     /// A nonsettable clock which is not affected discontinuous changes in the system clock
     Monotonic = CLOCK_MONOTONIC,
+    /// Like `Monotonic`, but also includes any time the system is suspended
+    Boottime = CLOCK_BOOTTIME,
+    /// Like `Realtime`, but can wake the system from suspend if the process holds `CAP_WAKE_ALARM`
+    RealtimeAlarm = CLOCK_REALTIME_ALARM,
+    /// Like `Boottime`, but can wake the system from suspend if the process holds `CAP_WAKE_ALARM`
+    BoottimeAlarm = CLOCK_BOOTTIME_ALARM,
 }
 
 /// A helper struct for creating, reading, and closing a `timerfd` instance.
@@ -105,7 +130,7 @@ pub enum ClockId {
 /// }
 /// ```
 #[derive(Debug)]
-pub struct TimerFd(RawFd);
+pub struct TimerFd(RawFd, Option<ClockId>);
 
 impl TimerFd {
     /// Create a new TimerFd
@@ -115,7 +140,10 @@ impl TimerFd {
 
     /// Create a new TimerFd with flags
     pub fn with_flags(clock_id: ClockId, flags: TFDFlags) -> nix::Result<TimerFd> {
-        Ok(TimerFd(timerfd_create(clock_id as clockid_t, flags)?))
+        Ok(TimerFd(
+            timerfd_create(clock_id as clockid_t, flags)?,
+            Some(clock_id),
+        ))
     }
 
     /// Start or stop a timer
@@ -142,6 +170,40 @@ impl TimerFd {
         timerfd_settime(self.0, flags, itmr, otmr)
     }
 
+    /// Arm (or re-arm) the timer with the given expiration
+    pub fn arm(&mut self, expiration: Expiration, flags: TFDTimerFlags) -> nix::Result<()> {
+        self.set_time_with_flags(flags, &expiration.into(), None)
+    }
+
+    /// Disarm the timer
+    pub fn unset(&mut self) -> nix::Result<()> {
+        self.set_time(&ITimerSpec::seconds(0), None)
+    }
+
+    /// Read back the timer's current expiration
+    pub fn get_expiration(&self) -> nix::Result<Expiration> {
+        let mut spec = ITimerSpec::seconds(0);
+        timerfd_gettime(self.0, &mut spec)?;
+        Ok(spec.into())
+    }
+
+    /// Duplicate the underlying file descriptor, yielding a second `TimerFd` that shares the
+    /// same armed timer
+    pub fn try_clone(&self) -> nix::Result<TimerFd> {
+        Ok(TimerFd(unistd::dup(self.0)?, self.1))
+    }
+
+    /// Query the resolution of the clock backing this timer.
+    ///
+    /// Fails with `Error::UnsupportedOperation` if this `TimerFd` was adopted via `from_raw_fd`,
+    /// since the clock backing an adopted fd cannot be recovered.
+    pub fn resolution(&self) -> nix::Result<TimeSpec> {
+        match self.1 {
+            Some(clock_id) => clock_resolution(clock_id),
+            None => Err(Error::UnsupportedOperation),
+        }
+    }
+
     pub fn read_time(&mut self) -> nix::Result<Option<u64>> {
         let mut buf: [u8; TIMERFD_DATA_SIZE] = unsafe { mem::uninitialized() };
 
@@ -168,6 +230,22 @@ impl AsRawFd for TimerFd {
     }
 }
 
+impl FromRawFd for TimerFd {
+    // The clock backing an adopted fd can't be recovered, so `resolution` reports
+    // `Error::UnsupportedOperation` for fds constructed this way rather than guessing.
+    unsafe fn from_raw_fd(fd: RawFd) -> TimerFd {
+        TimerFd(fd, None)
+    }
+}
+
+impl IntoRawFd for TimerFd {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.0;
+        mem::forget(self);
+        fd
+    }
+}
+
 impl Drop for TimerFd {
     fn drop(&mut self) {
         let _ = unistd::close(self.0);
@@ -180,6 +258,105 @@ mod tests {
     use std::{time, thread};
     use nix::sys::time::{TimeSpec, TimeValLike};
 
+    #[test]
+    fn test_resolution() {
+        let timer = TimerFd::new(ClockId::Monotonic).expect("Fail to create timerfd");
+        let res = timer.resolution().expect("Fail to query resolution");
+        assert!(res >= TimeSpec::zero());
+    }
+
+    #[test]
+    fn test_resolution_unknown_for_adopted_fd() {
+        let timer = TimerFd::new(ClockId::Monotonic).expect("Fail to create timerfd");
+        let adopted = unsafe { TimerFd::from_raw_fd(timer.into_raw_fd()) };
+        assert_eq!(adopted.resolution(), Err(Error::UnsupportedOperation));
+    }
+
+    #[test]
+    fn test_boottime_clock() {
+        TimerFd::new(ClockId::Boottime).expect("Fail to create timerfd with Boottime clock");
+    }
+
+    #[test]
+    fn test_alarm_clocks() {
+        // Creating an alarm-capable timerfd requires CAP_WAKE_ALARM, and some sandboxed kernels
+        // reject the alarm clocks outright (EINVAL); accept both so this test still passes for
+        // callers (e.g. CI) that can't exercise the real thing.
+        for &clock_id in &[ClockId::RealtimeAlarm, ClockId::BoottimeAlarm] {
+            match TimerFd::new(clock_id) {
+                Ok(_) => {}
+                Err(Error::Sys(Errno::EPERM)) => {}
+                Err(Error::Sys(Errno::EINVAL)) => {}
+                Err(err) => panic!("Fail to create timerfd with {:?} clock: {:?}", clock_id, err),
+            }
+        }
+    }
+
+    #[test]
+    fn test_try_clone_shares_armed_timer() {
+        let mut timer = TimerFd::new(ClockId::Monotonic).expect("Fail to create timerfd");
+        timer
+            .arm(Expiration::OneShot(TimeSpec::seconds(100)), Default::default())
+            .expect("Fail to arm timer");
+
+        let clone = timer.try_clone().expect("Fail to clone timerfd");
+        assert_ne!(timer.as_raw_fd(), clone.as_raw_fd());
+
+        // A `try_clone`d TimerFd dups the fd, so the two share the same underlying timer:
+        // disarming through one is visible through the other.
+        timer.unset().expect("Fail to unset timer");
+        assert_eq!(
+            clone.get_expiration(),
+            Ok(Expiration::OneShot(TimeSpec::zero()))
+        );
+    }
+
+    #[test]
+    fn test_into_raw_fd_and_from_raw_fd_round_trip() {
+        let mut timer = TimerFd::new(ClockId::Monotonic).expect("Fail to create timerfd");
+        timer
+            .arm(Expiration::OneShot(TimeSpec::seconds(100)), Default::default())
+            .expect("Fail to arm timer");
+
+        // `into_raw_fd` must suppress `Drop`'s close, or the fd below would already be closed
+        let fd = timer.into_raw_fd();
+        let adopted = unsafe { TimerFd::from_raw_fd(fd) };
+        match adopted.get_expiration().expect("Fail to get expiration") {
+            Expiration::OneShot(value) => {
+                assert!(value > TimeSpec::zero() && value <= TimeSpec::seconds(100))
+            }
+            other => panic!("expected OneShot, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_arm_and_get_expiration() {
+        let mut timer =
+            TimerFd::with_flags(ClockId::Monotonic, TFD_NONBLOCK).expect("Fail to create timerfd");
+
+        // `it_value` counts down to the next expiration, so a long delay is used to avoid
+        // flakiness from the time elapsed between arming and reading it back
+        timer
+            .arm(
+                Expiration::IntervalDelayed(TimeSpec::seconds(100), TimeSpec::seconds(1)),
+                Default::default(),
+            )
+            .expect("Fail to arm timer");
+        match timer.get_expiration().expect("Fail to get expiration") {
+            Expiration::IntervalDelayed(value, interval) => {
+                assert_eq!(interval, TimeSpec::seconds(1));
+                assert!(value > TimeSpec::zero() && value <= TimeSpec::seconds(100));
+            }
+            other => panic!("expected IntervalDelayed, got {:?}", other),
+        }
+
+        timer.unset().expect("Fail to unset timer");
+        assert_eq!(
+            timer.get_expiration(),
+            Ok(Expiration::OneShot(TimeSpec::zero()))
+        );
+    }
+
     #[test]
     fn test_read_timerfd() {
         let mut timer =
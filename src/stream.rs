@@ -0,0 +1,112 @@
+//! Async reactor integration for periodic timers, enabled by the `async` feature.
+extern crate futures;
+extern crate mio;
+extern crate tokio_reactor;
+
+use std::io;
+use std::os::unix::io::AsRawFd;
+use self::futures::{Async, Poll, Stream};
+use self::mio::unix::EventedFd;
+use self::mio::{Evented, Poll as MioPoll, PollOpt, Ready, Token};
+use self::tokio_reactor::PollEvented;
+
+use nix;
+use TimerFd;
+
+impl Evented for TimerFd {
+    fn register(
+        &self,
+        poll: &MioPoll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &MioPoll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &MioPoll) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).deregister(poll)
+    }
+}
+
+/// A `Stream` that yields the expiration count of a periodic `TimerFd` each time it fires.
+///
+/// The wrapped `TimerFd` must be created with `TFD_NONBLOCK` so that a spurious wakeup results
+/// in `EAGAIN` rather than blocking the reactor thread.
+pub struct TimerFdStream(PollEvented<TimerFd>);
+
+impl TimerFdStream {
+    /// Register a non-blocking `TimerFd` with the current reactor
+    pub fn new(timerfd: TimerFd) -> io::Result<TimerFdStream> {
+        Ok(TimerFdStream(PollEvented::new(timerfd)))
+    }
+}
+
+impl Stream for TimerFdStream {
+    type Item = u64;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if let Async::NotReady = self.0.poll_read_ready(Ready::readable())? {
+            return Ok(Async::NotReady);
+        }
+
+        match self.0.get_mut().read_time() {
+            Ok(Some(n)) => Ok(Async::Ready(Some(n))),
+            Ok(None) => {
+                self.0.clear_read_ready(Ready::readable())?;
+                Ok(Async::NotReady)
+            }
+            Err(err) => Err(to_io_error(err)),
+        }
+    }
+}
+
+fn to_io_error(err: nix::Error) -> io::Error {
+    match err {
+        nix::Error::Sys(errno) => io::Error::from_raw_os_error(errno as i32),
+        other => io::Error::other(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tokio;
+
+    use super::*;
+    use self::tokio::runtime::current_thread::Runtime;
+    use {ClockId, Expiration, TFD_NONBLOCK};
+    use nix::sys::time::{TimeSpec, TimeValLike};
+
+    #[test]
+    fn test_timer_fd_stream_ticks() {
+        // The first `poll` races the timer and is expected to find the fd not yet readable
+        // (Pending); the reactor then wakes the stream once the fd becomes readable ~20ms later.
+        let mut timerfd = TimerFd::with_flags(ClockId::Monotonic, TFD_NONBLOCK)
+            .expect("Fail to create timerfd");
+        timerfd
+            .arm(
+                Expiration::OneShot(TimeSpec::milliseconds(20)),
+                Default::default(),
+            )
+            .expect("Fail to arm timer");
+        let stream = TimerFdStream::new(timerfd).expect("Fail to register timerfd with reactor");
+
+        let ticks = Runtime::new()
+            .expect("Fail to create runtime")
+            .block_on(stream.take(1).collect())
+            .expect("Fail to drive the stream");
+
+        assert_eq!(ticks, vec![1]);
+    }
+}
@@ -1,4 +1,4 @@
-use libc::{c_int, clockid_t};
+use libc::{c_int, clockid_t, timespec};
 use itimer_spec::ITimerSpec;
 
 extern "C" {
@@ -10,4 +10,5 @@ extern "C" {
         otmr: *mut ITimerSpec,
     ) -> c_int;
     pub fn timerfd_gettime(ufd: c_int, otmr: *mut ITimerSpec) -> c_int;
+    pub fn clock_getres(clock_id: clockid_t, res: *mut timespec) -> c_int;
 }
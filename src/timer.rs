@@ -0,0 +1,261 @@
+//! A `Timer` abstraction that lets scheduling logic run against either a real `TimerFd` or a
+//! `FakeTimer` driven by a virtual clock, so it can be tested without sleeping in wall time.
+use std::os::unix::io::AsRawFd;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use nix;
+use nix::poll::{poll, PollFd, POLLIN};
+use nix::sys::time::{TimeSpec, TimeValLike};
+
+use {Expiration, ITimerSpec, TFDTimerFlags, TimerFd};
+
+/// The operations `TimerFd` performs, abstracted so test code can substitute a `FakeTimer`.
+pub trait Timer {
+    /// Arm (or re-arm) the timer with the given expiration
+    fn arm(&mut self, expiration: Expiration, flags: TFDTimerFlags) -> nix::Result<()>;
+
+    /// Disarm the timer
+    fn disarm(&mut self) -> nix::Result<()>;
+
+    /// Return the number of expirations that have elapsed since the last read, or `None` if
+    /// none have elapsed yet
+    fn read_expirations(&mut self) -> nix::Result<Option<u64>>;
+
+    /// Block until the timer has expired at least once, returning the expiration count
+    fn wait(&mut self) -> nix::Result<u64>;
+}
+
+impl Timer for TimerFd {
+    fn arm(&mut self, expiration: Expiration, flags: TFDTimerFlags) -> nix::Result<()> {
+        TimerFd::arm(self, expiration, flags)
+    }
+
+    fn disarm(&mut self) -> nix::Result<()> {
+        self.unset()
+    }
+
+    fn read_expirations(&mut self) -> nix::Result<Option<u64>> {
+        self.read_time()
+    }
+
+    fn wait(&mut self) -> nix::Result<u64> {
+        loop {
+            if let Some(n) = self.read_time()? {
+                return Ok(n);
+            }
+            // `read_time` only returned `None` when the fd is non-blocking (EAGAIN); block on
+            // the fd becoming readable instead of busy-spinning until it does.
+            let mut fds = [PollFd::new(self.as_raw_fd(), POLLIN)];
+            poll(&mut fds, -1)?;
+        }
+    }
+}
+
+/// A virtual clock shared between a `FakeTimer` and the test driving it
+#[derive(Clone, Debug)]
+pub struct FakeClock(Arc<Mutex<Duration>>);
+
+impl FakeClock {
+    /// Create a new clock starting at time zero
+    pub fn new() -> FakeClock {
+        FakeClock(Arc::new(Mutex::new(Duration::from_secs(0))))
+    }
+
+    /// Move the virtual clock forward, potentially causing armed `FakeTimer`s to expire
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.0.lock().unwrap();
+        *now += by;
+    }
+
+    fn now(&self) -> Duration {
+        *self.0.lock().unwrap()
+    }
+}
+
+impl Default for FakeClock {
+    fn default() -> Self {
+        FakeClock::new()
+    }
+}
+
+/// A `Timer` backed by a user-advanceable `FakeClock` instead of the kernel
+pub struct FakeTimer {
+    clock: FakeClock,
+    spec: Option<ITimerSpec>,
+    armed_at: Duration,
+    consumed: u64,
+}
+
+impl FakeTimer {
+    /// Create a new, disarmed timer driven by `clock`
+    pub fn new(clock: FakeClock) -> FakeTimer {
+        FakeTimer {
+            clock,
+            spec: None,
+            armed_at: Duration::from_secs(0),
+            consumed: 0,
+        }
+    }
+}
+
+impl Timer for FakeTimer {
+    fn arm(&mut self, expiration: Expiration, _flags: TFDTimerFlags) -> nix::Result<()> {
+        let spec: ITimerSpec = expiration.into();
+        if timespec_to_duration(spec.it_value) == Duration::from_secs(0) {
+            // Per timerfd_settime(2), an it_value of zero disarms the timer regardless of
+            // it_interval.
+            self.spec = None;
+            return Ok(());
+        }
+
+        self.spec = Some(spec);
+        self.armed_at = self.clock.now();
+        self.consumed = 0;
+        Ok(())
+    }
+
+    fn disarm(&mut self) -> nix::Result<()> {
+        self.spec = None;
+        Ok(())
+    }
+
+    fn read_expirations(&mut self) -> nix::Result<Option<u64>> {
+        let spec = match self.spec {
+            Some(spec) => spec,
+            None => return Ok(None),
+        };
+
+        let value = timespec_to_duration(spec.it_value);
+        let elapsed = self.clock
+            .now()
+            .checked_sub(self.armed_at)
+            .unwrap_or_else(|| Duration::from_secs(0));
+        if elapsed < value {
+            return Ok(None);
+        }
+
+        let interval = timespec_to_duration(spec.it_interval);
+        let total = if interval == Duration::from_secs(0) {
+            1
+        } else {
+            1 + duration_as_nanos(elapsed - value) / duration_as_nanos(interval)
+        };
+
+        let fired = total - self.consumed;
+        if fired == 0 {
+            return Ok(None);
+        }
+        self.consumed = total;
+
+        if interval == Duration::from_secs(0) {
+            // a zero interval means "one-shot"; disarm after it has fired once
+            self.spec = None;
+        }
+
+        Ok(Some(fired))
+    }
+
+    fn wait(&mut self) -> nix::Result<u64> {
+        loop {
+            if let Some(n) = self.read_expirations()? {
+                return Ok(n);
+            }
+        }
+    }
+}
+
+fn timespec_to_duration(ts: TimeSpec) -> Duration {
+    let nanos = ts.num_nanoseconds();
+    Duration::new((nanos / 1_000_000_000) as u64, (nanos % 1_000_000_000) as u32)
+}
+
+fn duration_as_nanos(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000_000 + duration.subsec_nanos() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use ClockId;
+
+    #[test]
+    fn test_timer_fd_wait_blocks_until_expiration() {
+        let mut timer = TimerFd::new(ClockId::Monotonic).expect("Fail to create timerfd");
+        Timer::arm(
+            &mut timer,
+            Expiration::OneShot(TimeSpec::milliseconds(50)),
+            Default::default(),
+        ).expect("Fail to arm timer");
+        assert_eq!(timer.wait(), Ok(1));
+    }
+
+    #[test]
+    fn test_fake_timer_wait_blocks_until_clock_advances() {
+        let clock = FakeClock::new();
+        let mut timer = FakeTimer::new(clock.clone());
+
+        timer
+            .arm(Expiration::OneShot(TimeSpec::milliseconds(50)), Default::default())
+            .unwrap();
+
+        let advancer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            clock.advance(Duration::from_millis(50));
+        });
+
+        assert_eq!(timer.wait(), Ok(1));
+        advancer.join().unwrap();
+    }
+
+    #[test]
+    fn test_fake_timer_one_shot() {
+        let clock = FakeClock::new();
+        let mut timer = FakeTimer::new(clock.clone());
+
+        timer
+            .arm(Expiration::OneShot(TimeSpec::seconds(3)), Default::default())
+            .unwrap();
+        assert_eq!(timer.read_expirations(), Ok(None));
+
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(timer.read_expirations(), Ok(Some(1)));
+        assert_eq!(timer.read_expirations(), Ok(None));
+    }
+
+    #[test]
+    fn test_fake_timer_zero_value_disarms() {
+        let clock = FakeClock::new();
+        let mut timer = FakeTimer::new(clock.clone());
+
+        // Per timerfd_settime(2), an it_value of zero disarms the timer regardless of
+        // it_interval, even for what would otherwise be a recurring `IntervalDelayed`.
+        timer
+            .arm(
+                Expiration::IntervalDelayed(TimeSpec::zero(), TimeSpec::seconds(1)),
+                Default::default(),
+            )
+            .unwrap();
+        assert_eq!(timer.read_expirations(), Ok(None));
+
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(timer.read_expirations(), Ok(None));
+    }
+
+    #[test]
+    fn test_fake_timer_interval_coalesces_missed_ticks() {
+        let clock = FakeClock::new();
+        let mut timer = FakeTimer::new(clock.clone());
+
+        timer
+            .arm(
+                Expiration::Interval(TimeSpec::seconds(1)),
+                Default::default(),
+            )
+            .unwrap();
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(timer.read_expirations(), Ok(Some(5)));
+        assert_eq!(timer.read_expirations(), Ok(None));
+    }
+}